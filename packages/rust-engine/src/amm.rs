@@ -0,0 +1,122 @@
+// Constant-product (Uniswap V2 style) swap simulation, used to size a
+// sandwich's attacker profit and victim slippage from pool reserves.
+
+use ethereum_types::{H160, U256};
+
+/// Reserves for a single pool, keyed by its two tokens (unordered).
+#[derive(Clone, Copy, Debug)]
+pub struct Pool {
+    pub token_a: H160,
+    pub reserve_a: U256,
+    pub token_b: H160,
+    pub reserve_b: U256,
+}
+
+impl Pool {
+    fn reserves_for(&self, token_in: H160, token_out: H160) -> Option<(U256, U256)> {
+        if self.token_a == token_in && self.token_b == token_out {
+            Some((self.reserve_a, self.reserve_b))
+        } else if self.token_b == token_in && self.token_a == token_out {
+            Some((self.reserve_b, self.reserve_a))
+        } else {
+            None
+        }
+    }
+
+    fn apply_trade(&mut self, token_in: H160, amount_in: U256, amount_out: U256) {
+        if self.token_a == token_in {
+            self.reserve_a += amount_in;
+            self.reserve_b = self.reserve_b.saturating_sub(amount_out);
+        } else {
+            self.reserve_b += amount_in;
+            self.reserve_a = self.reserve_a.saturating_sub(amount_out);
+        }
+    }
+}
+
+/// Uniswap V2's constant-product swap output, with the standard 0.3% fee:
+/// `amountOut = (amountIn * 997 * reserveOut) / (reserveIn * 1000 + amountIn * 997)`.
+///
+/// `amount_in` is decoded verbatim from untrusted calldata with no value
+/// bound, so the intermediate products can overflow `U256` for a crafted or
+/// just very large swap. Falls back to zero output rather than panicking —
+/// a single such transaction must not abort the whole batch.
+pub fn amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> U256 {
+    if amount_in.is_zero() || reserve_in.is_zero() || reserve_out.is_zero() {
+        return U256::zero();
+    }
+    checked_amount_out(amount_in, reserve_in, reserve_out).unwrap_or_else(U256::zero)
+}
+
+fn checked_amount_out(amount_in: U256, reserve_in: U256, reserve_out: U256) -> Option<U256> {
+    let amount_in_with_fee = amount_in.checked_mul(U256::from(997))?;
+    let numerator = amount_in_with_fee.checked_mul(reserve_out)?;
+    let denominator = reserve_in
+        .checked_mul(U256::from(1000))?
+        .checked_add(amount_in_with_fee)?;
+    if denominator.is_zero() {
+        return None;
+    }
+    Some(numerator / denominator)
+}
+
+/// Attacker profit and victim slippage from simulating a sandwich bundle.
+pub struct SandwichSimulation {
+    pub attacker_profit: U256,
+    pub victim_loss: U256,
+}
+
+/// Simulates `frontrun -> victim -> backrun` against `pool`'s reserves,
+/// mutating it as each leg executes. `token_in`/`token_out` are the
+/// front-run's trade direction; the back-run is assumed to trade the
+/// reverse direction.
+pub fn simulate_sandwich(
+    pool: &mut Pool,
+    token_in: H160,
+    token_out: H160,
+    frontrun_amount_in: U256,
+    victim_amount_in: U256,
+) -> Option<SandwichSimulation> {
+    let (reserve_in, reserve_out) = pool.reserves_for(token_in, token_out)?;
+
+    // Front-run buy: token_in -> token_out.
+    let frontrun_out = amount_out(frontrun_amount_in, reserve_in, reserve_out);
+    pool.apply_trade(token_in, frontrun_amount_in, frontrun_out);
+
+    // Victim trades against the reserves the front-run just moved.
+    let (post_front_in, post_front_out) = pool.reserves_for(token_in, token_out)?;
+    let victim_received = amount_out(victim_amount_in, post_front_in, post_front_out);
+    let victim_fair = amount_out(victim_amount_in, reserve_in, reserve_out);
+    let victim_loss = victim_fair.saturating_sub(victim_received);
+    pool.apply_trade(token_in, victim_amount_in, victim_received);
+
+    // Back-run sell: token_out -> token_in, unwinding the front-run.
+    let (back_reserve_in, back_reserve_out) = pool.reserves_for(token_out, token_in)?;
+    let backrun_out = amount_out(frontrun_out, back_reserve_in, back_reserve_out);
+    pool.apply_trade(token_out, frontrun_out, backrun_out);
+
+    Some(SandwichSimulation {
+        attacker_profit: backrun_out.saturating_sub(frontrun_amount_in),
+        victim_loss,
+    })
+}
+
+/// Simulates `amount_in` swapped sequentially through each hop of `path`,
+/// looking up each hop's pool via `pool_for`. Returns `None` if any hop's
+/// pool isn't configured. Used to price a single transaction's multi-hop
+/// route, e.g. a cyclic arbitrage `A -> B -> C -> A`. `amount_in` comes
+/// straight from decoded calldata, but each hop goes through [`amount_out`],
+/// which already falls back to zero on overflow rather than panicking.
+pub fn simulate_path(
+    path: &[H160],
+    amount_in: U256,
+    mut pool_for: impl FnMut(H160, H160) -> Option<Pool>,
+) -> Option<U256> {
+    let mut amount = amount_in;
+    for hop in path.windows(2) {
+        let pool = pool_for(hop[0], hop[1])?;
+        let (reserve_in, reserve_out) = pool.reserves_for(hop[0], hop[1])?;
+        amount = amount_out(amount, reserve_in, reserve_out);
+    }
+    Some(amount)
+}
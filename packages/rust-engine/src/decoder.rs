@@ -0,0 +1,209 @@
+// ABI decoding for the Uniswap V2 and V3 router swap functions we care about.
+// Calldata is hex-encoded (with or without a leading "0x") the same way it
+// arrives from Node.js in `Transaction::data`.
+
+use ethereum_types::{H160, U256};
+
+mod selectors {
+    // Uniswap V2 / PancakeSwap V2 router.
+    pub const SWAP_EXACT_TOKENS_FOR_TOKENS: &str = "38ed1739";
+    pub const SWAP_EXACT_ETH_FOR_TOKENS: &str = "7ff36ab5";
+    pub const SWAP_EXACT_TOKENS_FOR_ETH: &str = "18cbafe5";
+    // Uniswap V3 SwapRouter.
+    pub const EXACT_INPUT_SINGLE: &str = "04e45aaf";
+    pub const EXACT_INPUT: &str = "b858183f";
+}
+
+/// A decoded Uniswap-style swap call extracted from transaction calldata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Swap {
+    pub amount_in: U256,
+    pub amount_out_min: U256,
+    pub path: Vec<H160>,
+}
+
+/// ABI-decodes `data` as one of the supported router swap functions.
+/// Returns `None` if the selector isn't recognized or the calldata is malformed.
+pub fn decode_swap(data: &str) -> Option<Swap> {
+    let hex = data.strip_prefix("0x").unwrap_or(data);
+    if hex.len() < 8 {
+        return None;
+    }
+    let selector = hex[..8].to_lowercase();
+    let args = &hex[8..];
+
+    match selector.as_str() {
+        selectors::SWAP_EXACT_TOKENS_FOR_TOKENS | selectors::SWAP_EXACT_TOKENS_FOR_ETH => {
+            let amount_in = read_word_u256(args, 0)?;
+            let amount_out_min = read_word_u256(args, 1)?;
+            let path_offset = checked_usize(read_word_u256(args, 2)?, args.len() / 2)?;
+            let path = decode_path(args, path_offset)?;
+            Some(Swap { amount_in, amount_out_min, path })
+        }
+        selectors::SWAP_EXACT_ETH_FOR_TOKENS => {
+            // amountIn isn't part of the calldata for the ETH-in variant; it's the
+            // transaction's `value`, so callers should fill it in from there.
+            let amount_out_min = read_word_u256(args, 0)?;
+            let path_offset = checked_usize(read_word_u256(args, 1)?, args.len() / 2)?;
+            let path = decode_path(args, path_offset)?;
+            Some(Swap { amount_in: U256::zero(), amount_out_min, path })
+        }
+        selectors::EXACT_INPUT_SINGLE => decode_exact_input_single(args),
+        selectors::EXACT_INPUT => decode_exact_input(args),
+        _ => None,
+    }
+}
+
+/// SwapRouter02's `exactInputSingle((address tokenIn, address tokenOut,
+/// uint24 fee, address recipient, uint256 amountIn, uint256
+/// amountOutMinimum, uint160 sqrtPriceLimitX96))` (selector `0x04e45aaf` —
+/// the older overload with a `deadline` field is `0x414bf389` instead) — a
+/// single struct argument with no dynamic members, so its fields are just
+/// consecutive words after the selector.
+fn decode_exact_input_single(args: &str) -> Option<Swap> {
+    let token_in = read_word_address(args, 0)?;
+    let token_out = read_word_address(args, 1)?;
+    let amount_in = read_word_u256(args, 4)?;
+    let amount_out_min = read_word_u256(args, 5)?;
+    Some(Swap { amount_in, amount_out_min, path: vec![token_in, token_out] })
+}
+
+/// SwapRouter02's `exactInput((bytes path, address recipient, uint256
+/// amountIn, uint256 amountOutMinimum))` (selector `0xb858183f` — the older
+/// overload with a `deadline` field is `0xc04b8d59` instead). The struct
+/// has a dynamic `bytes` member so it's encoded as: a head offset to the
+/// struct, then inside the struct a head offset to the packed `path`
+/// bytes, then the fixed fields.
+fn decode_exact_input(args: &str) -> Option<Swap> {
+    let struct_offset = checked_usize(read_word_u256(args, 0)?, args.len() / 2)?;
+    if !struct_offset.is_multiple_of(32) {
+        return None;
+    }
+    let base = struct_offset / 32;
+
+    let path_offset = checked_usize(read_word_u256(args, base)?, args.len() / 2)?;
+    if !path_offset.is_multiple_of(32) {
+        return None;
+    }
+    let amount_in = read_word_u256(args, base + 2)?;
+    let amount_out_min = read_word_u256(args, base + 3)?;
+
+    let path_word = base + path_offset / 32;
+    let path_len_bytes = checked_usize(read_word_u256(args, path_word)?, args.len() / 2)?;
+    let path_bytes = hex_to_bytes(args.get(
+        (path_word + 1) * 64..(path_word + 1) * 64 + path_len_bytes * 2,
+    )?)?;
+    let path = decode_packed_path(&path_bytes)?;
+
+    Some(Swap { amount_in, amount_out_min, path })
+}
+
+/// Decodes a V3 multi-hop path packed as `token(20) | fee(3) | token(20) | fee(3) | ... | token(20)`.
+fn decode_packed_path(bytes: &[u8]) -> Option<Vec<H160>> {
+    if bytes.len() < 20 || !(bytes.len() - 20).is_multiple_of(23) {
+        return None;
+    }
+    let mut path = Vec::new();
+    let mut pos = 0;
+    loop {
+        let mut token = [0u8; 20];
+        token.copy_from_slice(bytes.get(pos..pos + 20)?);
+        path.push(H160(token));
+        pos += 20;
+        if pos == bytes.len() {
+            break;
+        }
+        pos += 3; // skip the uint24 fee tier
+    }
+    Some(path)
+}
+
+/// Returns the hex characters for 32-byte ABI word `word_index` (0-based).
+fn read_word_hex(args: &str, word_index: usize) -> Option<&str> {
+    let start = word_index * 64;
+    args.get(start..start + 64)
+}
+
+fn read_word_u256(args: &str, word_index: usize) -> Option<U256> {
+    let bytes = hex_to_bytes(read_word_hex(args, word_index)?)?;
+    Some(U256::from_big_endian(&bytes))
+}
+
+/// Converts a `U256` ABI word into a `usize`, rejecting anything above
+/// `bound` instead of panicking. Offsets and lengths come straight from
+/// untrusted calldata, and `U256::as_usize()` panics on overflow — a single
+/// malformed `data` string must make the transaction decode to `None`, not
+/// abort the whole batch.
+fn checked_usize(value: U256, bound: usize) -> Option<usize> {
+    if value > U256::from(bound) {
+        return None;
+    }
+    Some(value.low_u64() as usize)
+}
+
+fn read_word_address(args: &str, word_index: usize) -> Option<H160> {
+    let word = read_word_hex(args, word_index)?;
+    // Addresses are left-padded with 12 zero bytes within their 32-byte word.
+    hex_to_address(&word[24..])
+}
+
+fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+fn hex_to_address(hex: &str) -> Option<H160> {
+    let bytes = hex_to_bytes(hex)?;
+    if bytes.len() != 20 {
+        return None;
+    }
+    let mut out = [0u8; 20];
+    out.copy_from_slice(&bytes);
+    Some(H160(out))
+}
+
+/// Decodes the dynamic `address[] path` located at `offset_bytes` from the
+/// start of `args` (i.e. right after the 4-byte selector).
+fn decode_path(args: &str, offset_bytes: usize) -> Option<Vec<H160>> {
+    if !offset_bytes.is_multiple_of(32) {
+        return None;
+    }
+    let offset_words = offset_bytes / 32;
+    // Bounded by the remaining words in `args`: each element consumes a
+    // whole word, so the array can't have more elements than that — caps
+    // the `Vec::with_capacity` below against an attacker-controlled length.
+    let len = checked_usize(read_word_u256(args, offset_words)?, args.len() / 64)?;
+    let mut path = Vec::with_capacity(len);
+    for i in 0..len {
+        path.push(read_word_address(args, offset_words + 1 + i)?);
+    }
+    Some(path)
+}
+
+/// Whether `frontrun` and `backrun` form a reversed A->B->A path, i.e. the
+/// back-run undoes the front-run's trade.
+pub fn is_reversed_path(frontrun: &Swap, backrun: &Swap) -> bool {
+    frontrun.path.len() >= 2
+        && frontrun.path.len() == backrun.path.len()
+        && frontrun.path.iter().rev().eq(backrun.path.iter())
+}
+
+/// Whether `swap` trades the exact same direction as `other`, i.e. the same
+/// `tokenIn -> tokenOut` pair. A sandwich's victim must trade the front-run's
+/// direction, not just its pair — simulating the swap assumes the victim's
+/// `amount_in` is denominated in the front-run's `token_in`, which only holds
+/// if the victim bought the same way.
+pub fn same_direction(swap: &Swap, other: &Swap) -> bool {
+    let (Some(a_in), Some(a_out)) = (swap.path.first(), swap.path.last()) else {
+        return false;
+    };
+    let (Some(b_in), Some(b_out)) = (other.path.first(), other.path.last()) else {
+        return false;
+    };
+    (a_in, a_out) == (b_in, b_out)
+}
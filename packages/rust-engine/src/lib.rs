@@ -1,41 +1,189 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
 use ethereum_types::{H160, U256};
-use std::collections::HashMap;
+
+mod decoder;
+
+mod amm;
+use amm::Pool;
+
+mod matcher;
 
 // A simplified Transaction struct, deserialized from JSON sent by Node.js.
 // We rename "from" to "sender" because "from" is a Rust keyword.
 #[derive(Deserialize, Serialize, Clone, Debug)]
-struct Transaction {
-    hash: String,
+pub(crate) struct Transaction {
+    pub(crate) hash: String,
     #[serde(rename = "from")]
-    sender: H160,
-    to: Option<H160>,
-    value: U256,
-    data: String,
+    pub(crate) sender: H160,
+    pub(crate) to: Option<H160>,
+    pub(crate) value: U256,
+    pub(crate) data: String,
     #[serde(rename = "gasPrice")]
-    gas_price: U256,
+    pub(crate) gas_price: U256,
+    // Position of this transaction within its block, when known (mined-block
+    // input). Raw mempool batches won't have this, so it's optional; its
+    // presence switches the matcher from the gas-price heuristic to ordering.
+    #[serde(rename = "blockIndex")]
+    pub(crate) block_index: Option<u64>,
+}
+
+// The output struct representing a detected sandwich attack.
+#[derive(Serialize, Debug)]
+pub(crate) struct SandwichAttack {
+    pub(crate) victim_tx_hash: String,
+    pub(crate) attacker: H160,
+    pub(crate) frontrun_tx_hash: String,
+    pub(crate) backrun_tx_hash: String,
+    pub(crate) attacker_profit: U256,
+    pub(crate) victim_loss: U256,
 }
 
-// The output struct representing a detected attack.
+/// A single transaction that routes through a closed loop of pools
+/// (`path[0] == path[path.len() - 1]`) to extract a price discrepancy.
 #[derive(Serialize, Debug)]
-struct SandwichAttack {
-    victim_tx_hash: String,
-    attacker: H160,
-    frontrun_tx_hash: String,
-    backrun_tx_hash: String,
+pub(crate) struct ArbitrageAttack {
+    pub(crate) tx_hash: String,
+    pub(crate) trader: H160,
+    pub(crate) path: Vec<H160>,
+    pub(crate) amount_in: U256,
+    pub(crate) amount_out: U256,
+    pub(crate) profit: U256,
+}
+
+/// The JSON payload `detect_mev` returns: a mixed list of every MEV
+/// occurrence found, tagged by `type` so downstream Node.js code can
+/// classify each entry without a second decoding pass.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(crate) enum MevAttack {
+    Sandwich(SandwichAttack),
+    Arbitrage(ArbitrageAttack),
+}
+
+/// Reserves for a single pool at the time of the bundle, keyed by its two
+/// tokens (unordered), used to simulate a candidate sandwich's P&L.
+#[derive(Deserialize, Clone, Debug)]
+struct PairReserves {
+    #[serde(rename = "tokenA")]
+    token_a: H160,
+    #[serde(rename = "tokenB")]
+    token_b: H160,
+    #[serde(rename = "reserveA")]
+    reserve_a: U256,
+    #[serde(rename = "reserveB")]
+    reserve_b: U256,
 }
 
-// A well-known address for the Uniswap V2 Router.
+// A well-known address for the Uniswap V2 Router. Used as the default router
+// set when the caller doesn't supply a `DetectorConfig`.
 const UNISWAP_V2_ROUTER: H160 = H160([
     0x7a, 0x25, 0x09, 0x56, 0x80, 0x8f, 0x5c, 0x3d, 0x7c, 0x48,
     0x53, 0x74, 0x0a, 0x6d, 0x7e, 0x44, 0x4e, 0x9a, 0xce, 0xd8
 ]);
 
+// Wrapped Ether, used as the default `wrappedNative` when no config is given.
+const WETH: H160 = H160([
+    0xc0, 0x2a, 0xaa, 0x39, 0xb2, 0x23, 0xfe, 0x8d, 0x0a, 0x0e,
+    0x5c, 0x4f, 0x27, 0xea, 0xd9, 0x08, 0x3c, 0x75, 0x6c, 0xc2
+]);
+
+/// Which ABI a router on the configured list speaks, so callers know which
+/// decoder path (and selectors) to expect from it.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum RouterKind {
+    V2,
+    V3,
+}
+
+/// A single recognized router, e.g. Uniswap V2 on Ethereum or PancakeSwap V2 on BSC.
+#[derive(Deserialize, Clone, Debug)]
+struct RouterEntry {
+    router: H160,
+    // Not read internally yet — `decode_swap` dispatches purely on the
+    // calldata's function selector, which is unambiguous on its own. Kept
+    // on the entry so the JSON schema documents which ABI each configured
+    // router speaks.
+    #[allow(dead_code)]
+    kind: RouterKind,
+}
+
+/// Caller-supplied configuration so the detector isn't hard-wired to a single
+/// chain/venue. Passed as an optional second JSON argument to `detect_mev`;
+/// when omitted, falls back to Uniswap V2 on Ethereum mainnet.
+#[derive(Deserialize, Clone, Debug)]
+pub(crate) struct DetectorConfig {
+    routers: Vec<RouterEntry>,
+    // Not read internally yet — reserved for chain-specific native-token
+    // handling (e.g. recognizing native-in swaps without a `data` amountIn).
+    #[allow(dead_code)]
+    #[serde(rename = "wrappedNative")]
+    wrapped_native: H160,
+    #[serde(default)]
+    reserves: Vec<PairReserves>,
+    // Minimum simulated attacker profit (in the native/quote token) for a
+    // candidate sandwich to be emitted. Defaults to 0, i.e. unfiltered.
+    #[serde(default, rename = "profitThreshold")]
+    profit_threshold: U256,
+    // Simulated profit a candidate cyclic arbitrage must exceed to be
+    // emitted. Defaults to 0, i.e. any profitable loop is emitted.
+    #[serde(default, rename = "arbitrageProfitThreshold")]
+    pub(crate) arbitrage_profit_threshold: U256,
+    // Maximum distance, in block-index positions, a front-run or back-run may
+    // sit from its victim when ordering by mined-block position (a sandwich
+    // is three adjacent-or-near-adjacent transactions, not any pair of
+    // earlier/later same-pair trades in the block). Only applies to the
+    // ordering mode; irrelevant when falling back to the gas-price heuristic
+    // because no block index was supplied.
+    #[serde(default = "default_max_block_index_gap", rename = "maxBlockIndexGap")]
+    pub(crate) max_block_index_gap: u64,
+}
+
+fn default_max_block_index_gap() -> u64 {
+    2
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        DetectorConfig {
+            routers: vec![RouterEntry { router: UNISWAP_V2_ROUTER, kind: RouterKind::V2 }],
+            wrapped_native: WETH,
+            reserves: Vec::new(),
+            profit_threshold: U256::zero(),
+            arbitrage_profit_threshold: U256::zero(),
+            max_block_index_gap: default_max_block_index_gap(),
+        }
+    }
+}
+
+impl DetectorConfig {
+    pub(crate) fn is_recognized_router(&self, to: Option<H160>) -> bool {
+        match to {
+            Some(addr) => self.routers.iter().any(|r| r.router == addr),
+            None => false,
+        }
+    }
+
+    /// Looks up the configured reserves for the pool trading `token_a`/`token_b`.
+    pub(crate) fn pool_for(&self, token_a: H160, token_b: H160) -> Option<Pool> {
+        self.reserves.iter().find_map(|r| {
+            if (r.token_a == token_a && r.token_b == token_b)
+                || (r.token_a == token_b && r.token_b == token_a)
+            {
+                Some(Pool { token_a: r.token_a, reserve_a: r.reserve_a, token_b: r.token_b, reserve_b: r.reserve_b })
+            } else {
+                None
+            }
+        })
+    }
+}
+
 // This is the exported function that will be called from Node.js.
-// It accepts a JSON string of a transaction batch and returns a JSON string of detected attacks.
+// It accepts a JSON string of a transaction batch and an optional JSON
+// `DetectorConfig`, and returns a JSON string of detected attacks.
 #[wasm_bindgen]
-pub fn detect_mev(tx_batch_json: &str) -> String {
+pub fn detect_mev(tx_batch_json: &str, config_json: Option<String>) -> String {
     // Improves debugging by logging Rust panics to the browser console.
     console_error_panic_hook::set_once();
 
@@ -43,73 +191,20 @@ pub fn detect_mev(tx_batch_json: &str) -> String {
         Ok(txs) => txs,
         Err(_) => return "[]".to_string(), // Return empty array on parsing error
     };
+    let config: DetectorConfig = config_json
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
 
-    let mut detected_attacks = Vec::new();
-    // Use a HashMap for faster lookups of transactions by their properties.
-    let tx_map: HashMap<String, Transaction> = transactions
-        .iter()
-        .map(|tx| (tx.hash.clone(), tx.clone()))
+    let mut detected_attacks: Vec<MevAttack> = matcher::detect_sandwiches(&transactions, &config)
+        .into_iter()
+        .map(MevAttack::Sandwich)
         .collect();
-
-    // This is a simplified triple loop. For production, you'd optimize this by
-    // pre-sorting and indexing transactions by the assets they interact with.
-    for potential_victim in &transactions {
-        // Condition 1: Is this a swap on Uniswap V2?
-        if potential_victim.to != Some(UNISWAP_V2_ROUTER) {
-            continue;
-        }
-
-        for potential_frontrun in &transactions {
-            // Condition 2: Is this a potential front-run?
-            // Same destination, higher gas price, different sender.
-            if potential_frontrun.to != Some(UNISWAP_V2_ROUTER) ||
-               potential_frontrun.sender == potential_victim.sender ||
-               potential_frontrun.gas_price <= potential_victim.gas_price {
-                continue;
-            }
-
-            for potential_backrun in &transactions {
-                // Condition 3: Is this a potential back-run?
-                // Sender must match the front-runner.
-                // Gas price must be lower than the victim's to execute after.
-                if potential_backrun.sender != potential_frontrun.sender ||
-                   potential_backrun.gas_price >= potential_victim.gas_price {
-                    continue;
-                }
-
-                // Heuristic: A sandwich involves trading the same assets.
-                // This is a simplified check. A robust solution would decode the
-                // 'data' field to extract the exact token path.
-                if a_b_a_path_matches(&potential_frontrun.data, &potential_backrun.data) {
-                    detected_attacks.push(SandwichAttack {
-                        victim_tx_hash: potential_victim.hash.clone(),
-                        attacker: potential_frontrun.sender,
-                        frontrun_tx_hash: potential_frontrun.hash.clone(),
-                        backrun_tx_hash: potential_backrun.hash.clone(),
-                    });
-                }
-            }
-        }
-    }
+    detected_attacks.extend(
+        matcher::detect_arbitrage(&transactions, &config)
+            .into_iter()
+            .map(MevAttack::Arbitrage),
+    );
 
     serde_json::to_string(&detected_attacks).unwrap_or_else(|_| "[]".to_string())
 }
-
-// A simple heuristic to check if a front-run and back-run form an A->B->A trade pattern.
-// e.g., Front-run: ETH->TOKEN_X, Back-run: TOKEN_X->ETH
-fn a_b_a_path_matches(frontrun_data: &str, backrun_data: &str) -> bool {
-    // A production implementation requires a proper ABI decoder.
-    // Here we make a simplifying assumption: the token paths are at the end of the calldata.
-    if frontrun_data.len() < 74 || backrun_data.len() < 74 {
-        return false;
-    }
-    // Extract last two tokens in path for frontrun
-    let front_token_a = &frontrun_data[frontrun_data.len() - 128..frontrun_data.len() - 64];
-    let front_token_b = &frontrun_data[frontrun_data.len() - 64..];
-    // Extract last two tokens in path for backrun
-    let back_token_a = &backrun_data[backrun_data.len() - 128..backrun_data.len() - 64];
-    let back_token_b = &backrun_data[backrun_data.len() - 64..];
-
-    // Check if path is reversed: front(A->B) and back(B->A)
-    front_token_a == back_token_b && front_token_b == back_token_a
-}
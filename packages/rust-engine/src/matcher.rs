@@ -0,0 +1,242 @@
+// Indexed sandwich matching: buckets transactions by the token pair they
+// trade so candidate front/back-runs for a victim only come from the same
+// bucket, then scans victims in parallel. The old triple loop scanned every
+// (victim, frontrun, backrun) triple in the whole batch; this collapses the
+// search to near-linear for realistic mempool batches.
+
+use ethereum_types::H160;
+use std::collections::HashMap;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::amm;
+use crate::decoder::{self, Swap};
+use crate::{ArbitrageAttack, DetectorConfig, SandwichAttack, Transaction};
+
+/// An unordered pair of tokens, used as the bucket key.
+type PairKey = (H160, H160);
+
+/// Whether `candidate` could be the front-run ahead of `victim`. When both
+/// carry a `block_index` (mined-block input), position in the block decides
+/// order — this also catches bundles that pay the builder directly instead
+/// of bidding through gas price, which share the victim's gas price. Raw
+/// mempool batches have no block index yet, so fall back to the classic
+/// higher-gas-price heuristic. The ordering mode additionally requires the
+/// front-run to sit within `config.max_block_index_gap` positions of the
+/// victim — a sandwich is three near-adjacent transactions, not any earlier
+/// same-pair trade in the block.
+fn is_valid_frontrun(candidate: &Transaction, victim: &Transaction, config: &DetectorConfig) -> bool {
+    if candidate.sender == victim.sender {
+        return false;
+    }
+    match (candidate.block_index, victim.block_index) {
+        (Some(c), Some(v)) => c < v && v - c <= config.max_block_index_gap,
+        _ => candidate.gas_price > victim.gas_price,
+    }
+}
+
+/// Whether `candidate` could be the back-run behind `victim`, mirroring
+/// [`is_valid_frontrun`]'s ordering-vs-gas-price fallback and index-gap bound.
+fn is_valid_backrun(
+    candidate: &Transaction,
+    frontrun: &Transaction,
+    victim: &Transaction,
+    config: &DetectorConfig,
+) -> bool {
+    if candidate.sender != frontrun.sender {
+        return false;
+    }
+    match (candidate.block_index, victim.block_index) {
+        (Some(c), Some(v)) => c > v && c - v <= config.max_block_index_gap,
+        _ => candidate.gas_price < victim.gas_price,
+    }
+}
+
+fn pair_key(swap: &Swap) -> Option<PairKey> {
+    let (&a, &b) = (swap.path.first()?, swap.path.last()?);
+    Some(if a.as_bytes() <= b.as_bytes() { (a, b) } else { (b, a) })
+}
+
+/// Buckets of transaction indices by the pair they trade, limited to
+/// transactions that hit a recognized router and decode successfully.
+fn build_buckets(swaps: &[Option<Swap>]) -> HashMap<PairKey, Vec<usize>> {
+    let mut buckets: HashMap<PairKey, Vec<usize>> = HashMap::new();
+    for (idx, swap) in swaps.iter().enumerate() {
+        if let Some(key) = swap.as_ref().and_then(pair_key) {
+            buckets.entry(key).or_default().push(idx);
+        }
+    }
+    buckets
+}
+
+/// Finds every sandwich in `transactions` where `victim_idx` is the victim,
+/// restricting the front/back-run search to transactions in the same pair
+/// bucket.
+fn find_sandwiches_for_victim(
+    victim_idx: usize,
+    transactions: &[Transaction],
+    swaps: &[Option<Swap>],
+    buckets: &HashMap<PairKey, Vec<usize>>,
+    config: &DetectorConfig,
+) -> Vec<SandwichAttack> {
+    let mut found = Vec::new();
+    let potential_victim = &transactions[victim_idx];
+    let Some(victim_swap) = swaps[victim_idx].as_ref() else {
+        return found;
+    };
+    let Some(key) = pair_key(victim_swap) else {
+        return found;
+    };
+    let Some(candidates) = buckets.get(&key) else {
+        return found;
+    };
+
+    for &frontrun_idx in candidates {
+        if frontrun_idx == victim_idx {
+            continue;
+        }
+        let potential_frontrun = &transactions[frontrun_idx];
+        if !config.is_recognized_router(potential_frontrun.to)
+            || !is_valid_frontrun(potential_frontrun, potential_victim, config)
+        {
+            continue;
+        }
+        let Some(frontrun_swap) = swaps[frontrun_idx].as_ref() else {
+            continue;
+        };
+
+        for &backrun_idx in candidates {
+            if backrun_idx == victim_idx || backrun_idx == frontrun_idx {
+                continue;
+            }
+            let potential_backrun = &transactions[backrun_idx];
+            if !is_valid_backrun(potential_backrun, potential_frontrun, potential_victim, config) {
+                continue;
+            }
+            let Some(backrun_swap) = swaps[backrun_idx].as_ref() else {
+                continue;
+            };
+
+            // A sandwich requires the front/back-run to undo each other's
+            // trade (reversed path), and the victim to have traded the same
+            // direction as the front-run (not just the same pair) so the
+            // simulated amounts below are denominated consistently.
+            if !decoder::is_reversed_path(frontrun_swap, backrun_swap)
+                || !decoder::same_direction(frontrun_swap, victim_swap)
+            {
+                continue;
+            }
+            let (Some(&token_in), Some(&token_out)) =
+                (frontrun_swap.path.first(), frontrun_swap.path.last())
+            else {
+                continue;
+            };
+            // ETH-in swaps carry amountIn as the tx value rather than in the calldata.
+            let frontrun_amount_in = if frontrun_swap.amount_in.is_zero() {
+                potential_frontrun.value
+            } else {
+                frontrun_swap.amount_in
+            };
+            let victim_amount_in = if victim_swap.amount_in.is_zero() {
+                potential_victim.value
+            } else {
+                victim_swap.amount_in
+            };
+
+            let simulation = config.pool_for(token_in, token_out).and_then(|mut pool| {
+                amm::simulate_sandwich(&mut pool, token_in, token_out, frontrun_amount_in, victim_amount_in)
+            });
+            let (attacker_profit, victim_loss) = match simulation {
+                Some(sim) => (sim.attacker_profit, sim.victim_loss),
+                None => Default::default(),
+            };
+            if attacker_profit < config.profit_threshold {
+                continue;
+            }
+
+            found.push(SandwichAttack {
+                victim_tx_hash: potential_victim.hash.clone(),
+                attacker: potential_frontrun.sender,
+                frontrun_tx_hash: potential_frontrun.hash.clone(),
+                backrun_tx_hash: potential_backrun.hash.clone(),
+                attacker_profit,
+                victim_loss,
+            });
+        }
+    }
+
+    found
+}
+
+/// Detects all sandwiches in `transactions`. Buckets transactions by traded
+/// pair, then scans each victim's bucket for a matching front/back-run. Victims
+/// are scanned concurrently via rayon (sized to the available CPUs) unless the
+/// `parallel` feature is disabled, e.g. for the single-threaded WASM target.
+pub(crate) fn detect_sandwiches(transactions: &[Transaction], config: &DetectorConfig) -> Vec<SandwichAttack> {
+    let swaps: Vec<Option<Swap>> = transactions
+        .iter()
+        .map(|tx| {
+            if config.is_recognized_router(tx.to) {
+                decoder::decode_swap(&tx.data)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let buckets = build_buckets(&swaps);
+    let victim_indices: Vec<usize> = (0..transactions.len())
+        .filter(|&idx| swaps[idx].is_some())
+        .collect();
+
+    #[cfg(feature = "parallel")]
+    {
+        victim_indices
+            .par_iter()
+            .flat_map(|&idx| find_sandwiches_for_victim(idx, transactions, &swaps, &buckets, config))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        victim_indices
+            .iter()
+            .flat_map(|&idx| find_sandwiches_for_victim(idx, transactions, &swaps, &buckets, config))
+            .collect()
+    }
+}
+
+/// Detects cyclic arbitrage: a single transaction whose decoded path starts
+/// and ends at the same token (`A -> B -> C -> A`) and whose simulated
+/// constant-product output exceeds its input by more than
+/// `config.arbitrage_profit_threshold`.
+pub(crate) fn detect_arbitrage(transactions: &[Transaction], config: &DetectorConfig) -> Vec<ArbitrageAttack> {
+    transactions
+        .iter()
+        .filter_map(|tx| {
+            if !config.is_recognized_router(tx.to) {
+                return None;
+            }
+            let swap = decoder::decode_swap(&tx.data)?;
+            let first = *swap.path.first()?;
+            let last = *swap.path.last()?;
+            if swap.path.len() < 3 || first != last {
+                return None;
+            }
+            let amount_in = if swap.amount_in.is_zero() { tx.value } else { swap.amount_in };
+            let amount_out = amm::simulate_path(&swap.path, amount_in, |a, b| config.pool_for(a, b))?;
+            let profit = amount_out.checked_sub(amount_in)?;
+            if profit <= config.arbitrage_profit_threshold {
+                return None;
+            }
+
+            Some(ArbitrageAttack {
+                tx_hash: tx.hash.clone(),
+                trader: tx.sender,
+                path: swap.path,
+                amount_in,
+                amount_out,
+                profit,
+            })
+        })
+        .collect()
+}